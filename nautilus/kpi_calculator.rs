@@ -1,8 +1,14 @@
 use serde::{Deserialize, Serialize};
+use serde_big_array::BigArray;
 use serde_json::{Value};
 use sha2::{Sha256, Digest};
-use ed25519_dalek::{Keypair, Signer};
+use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
 use std::time::{SystemTime, UNIX_EPOCH};
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    pairing, G1Affine, G1Projective, G2Affine, G2Projective,
+};
+use group::Curve;
 
 /// KPI calculation result (without attestation)
 #[derive(Default, Serialize, Deserialize)]
@@ -12,26 +18,85 @@ pub struct KPIResult {
     pub file_type: String,
 }
 
-/// TEE Attestation structure (144 bytes)
-/// Format:
+/// Wire-format schema for a serialized `TEEAttestation`. `to_bytes` emits this as a
+/// single leading discriminant byte so the payload layout can grow without breaking
+/// decoders written against an earlier version.
+///
+/// - `V0`: the original fixed 144-byte payload (kept for backward compatibility).
+/// - `V1`: the `V0` payload plus an 8-byte little-endian replay-protection nonce
+///   (152 bytes), used when `TEEAttestation.nonce` is set.
+///
+/// Reserve higher values for longer payloads as further capabilities are added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttestationVersion {
+    V0 = 0,
+    V1 = 1,
+}
+
+impl AttestationVersion {
+    fn from_byte(byte: u8) -> Result<Self, AttestationError> {
+        match byte {
+            0 => Ok(AttestationVersion::V0),
+            1 => Ok(AttestationVersion::V1),
+            other => Err(AttestationError::UnknownVersion(other)),
+        }
+    }
+}
+
+/// Errors produced while decoding a versioned attestation envelope.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AttestationError {
+    /// The envelope was empty; there was no version byte to read.
+    Empty,
+    /// The leading version byte did not match any known `AttestationVersion`.
+    UnknownVersion(u8),
+    /// The payload length did not match what the version's schema expects.
+    InvalidLength { version: u8, expected: usize, got: usize },
+}
+
+impl std::fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttestationError::Empty => write!(f, "attestation bytes are empty"),
+            AttestationError::UnknownVersion(v) => write!(f, "unknown attestation version: {v}"),
+            AttestationError::InvalidLength { version, expected, got } => write!(
+                f,
+                "attestation version {version} expects {expected} payload bytes, got {got}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AttestationError {}
+
+/// TEE Attestation structure. Version 0's payload is 144 bytes:
 /// - kpi_value: u64 (8 bytes, little-endian)
-/// - computation_hash: 32 bytes (SHA-256)
+/// - computation_hash: 32 bytes (Merkle root over the input documents, see
+///   `calculate_documents_hash` / `inclusion_proof`)
 /// - timestamp: u64 (8 bytes, little-endian)
 /// - tee_public_key: 32 bytes
 /// - signature: 64 bytes (Ed25519)
-#[derive(Serialize, Deserialize)]
+///
+/// `nonce` is not part of the v0 wire payload; when present it is folded into
+/// the signed message (see `attestation_signing_preimage`) and appended as an extra
+/// 8 bytes under `AttestationVersion::V1` for replay protection.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct TEEAttestation {
     pub kpi_value: u64,
     pub computation_hash: [u8; 32],
     pub timestamp: u64,
     pub tee_public_key: [u8; 32],
+    #[serde(with = "BigArray")]
     pub signature: [u8; 64],
+    pub nonce: Option<u64>,
 }
 
+const V0_PAYLOAD_LEN: usize = 144;
+const V1_PAYLOAD_LEN: usize = V0_PAYLOAD_LEN + 8;
+
 impl TEEAttestation {
-    /// Convert attestation to bytes (144 bytes)
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(144);
+    fn to_bytes_v0(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(V0_PAYLOAD_LEN);
 
         // kpi_value (8 bytes, little-endian)
         bytes.extend_from_slice(&self.kpi_value.to_le_bytes());
@@ -48,9 +113,101 @@ impl TEEAttestation {
         // signature (64 bytes)
         bytes.extend_from_slice(&self.signature);
 
-        assert_eq!(bytes.len(), 144, "Attestation must be exactly 144 bytes");
+        assert_eq!(bytes.len(), V0_PAYLOAD_LEN, "v0 payload must be exactly 144 bytes");
         bytes
     }
+
+    /// Convert attestation to bytes: a leading version byte followed by the
+    /// version's payload. Emits `AttestationVersion::V0` (145 bytes total) when
+    /// `nonce` is `None`, or `AttestationVersion::V1` (153 bytes total, with the
+    /// nonce appended) when it is set.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self.nonce {
+            None => {
+                let mut bytes = Vec::with_capacity(1 + V0_PAYLOAD_LEN);
+                bytes.push(AttestationVersion::V0 as u8);
+                bytes.extend_from_slice(&self.to_bytes_v0());
+                bytes
+            }
+            Some(nonce) => {
+                let mut bytes = Vec::with_capacity(1 + V1_PAYLOAD_LEN);
+                bytes.push(AttestationVersion::V1 as u8);
+                bytes.extend_from_slice(&self.to_bytes_v0());
+                bytes.extend_from_slice(&nonce.to_le_bytes());
+                bytes
+            }
+        }
+    }
+
+    fn v0_fields_from_payload(payload: &[u8]) -> (u64, [u8; 32], u64, [u8; 32], [u8; 64]) {
+        let mut computation_hash = [0u8; 32];
+        computation_hash.copy_from_slice(&payload[8..40]);
+
+        let mut tee_public_key = [0u8; 32];
+        tee_public_key.copy_from_slice(&payload[48..80]);
+
+        let mut signature = [0u8; 64];
+        signature.copy_from_slice(&payload[80..144]);
+
+        (
+            u64::from_le_bytes(payload[0..8].try_into().unwrap()),
+            computation_hash,
+            u64::from_le_bytes(payload[40..48].try_into().unwrap()),
+            tee_public_key,
+            signature,
+        )
+    }
+
+    /// Parse a versioned attestation envelope produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<TEEAttestation, AttestationError> {
+        let (&version_byte, payload) = bytes.split_first().ok_or(AttestationError::Empty)?;
+
+        match AttestationVersion::from_byte(version_byte)? {
+            AttestationVersion::V0 => {
+                if payload.len() != V0_PAYLOAD_LEN {
+                    return Err(AttestationError::InvalidLength {
+                        version: version_byte,
+                        expected: V0_PAYLOAD_LEN,
+                        got: payload.len(),
+                    });
+                }
+
+                let (kpi_value, computation_hash, timestamp, tee_public_key, signature) =
+                    Self::v0_fields_from_payload(payload);
+
+                Ok(TEEAttestation {
+                    kpi_value,
+                    computation_hash,
+                    timestamp,
+                    tee_public_key,
+                    signature,
+                    nonce: None,
+                })
+            }
+            AttestationVersion::V1 => {
+                if payload.len() != V1_PAYLOAD_LEN {
+                    return Err(AttestationError::InvalidLength {
+                        version: version_byte,
+                        expected: V1_PAYLOAD_LEN,
+                        got: payload.len(),
+                    });
+                }
+
+                let (kpi_value, computation_hash, timestamp, tee_public_key, signature) =
+                    Self::v0_fields_from_payload(&payload[..V0_PAYLOAD_LEN]);
+                let nonce = u64::from_le_bytes(payload[V0_PAYLOAD_LEN..V1_PAYLOAD_LEN].try_into().unwrap());
+
+                Ok(TEEAttestation {
+                    kpi_value,
+                    computation_hash,
+                    timestamp,
+                    tee_public_key,
+                    signature,
+                    nonce: Some(nonce),
+                })
+            }
+        }
+    }
 }
 
 /// Complete KPI result with TEE attestation
@@ -58,7 +215,7 @@ impl TEEAttestation {
 pub struct KPIResultWithAttestation {
     pub kpi_result: KPIResult,
     pub attestation: TEEAttestation,
-    pub attestation_bytes: Vec<u8>, // 144 bytes for easy blockchain submission
+    pub attestation_bytes: Vec<u8>, // versioned envelope, see `TEEAttestation::to_bytes`
 }
 
 // 判斷檔案類型
@@ -124,10 +281,19 @@ fn process_overhead(data: &Value) -> f64 {
     -(overhead * 0.1)
 }
 
-/// Calculate hash of input documents for attestation
-fn calculate_documents_hash(documents_json: &str) -> [u8; 32] {
+/// Inclusion proof for a single document against a `TEEAttestation.computation_hash`
+/// Merkle root. `siblings` is ordered from the leaf's level up to the root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub leaf_index: usize,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// Leaf hash: SHA256(0x00 || canonical_document_bytes)
+fn merkle_leaf_hash(document_bytes: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::new();
-    hasher.update(documents_json.as_bytes());
+    hasher.update([0x00]);
+    hasher.update(document_bytes);
     let result = hasher.finalize();
 
     let mut hash = [0u8; 32];
@@ -135,6 +301,202 @@ fn calculate_documents_hash(documents_json: &str) -> [u8; 32] {
     hash
 }
 
+/// Internal node hash: SHA256(0x01 || left || right)
+fn merkle_parent_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    let result = hasher.finalize();
+
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&result);
+    hash
+}
+
+/// Build every level of the Merkle tree, leaves first. Odd levels duplicate
+/// their last node before pairing, so `tree.last()` is always `[root]`.
+fn build_merkle_levels(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+
+    while levels.last().unwrap().len() > 1 {
+        let current = levels.last().unwrap();
+        let mut next = Vec::with_capacity(current.len().div_ceil(2));
+
+        let mut i = 0;
+        while i < current.len() {
+            let left = &current[i];
+            let right = if i + 1 < current.len() { &current[i + 1] } else { left };
+            next.push(merkle_parent_hash(left, right));
+            i += 2;
+        }
+
+        levels.push(next);
+    }
+
+    levels
+}
+
+/// Canonical number representation: normalizes `50000` and `50000.0` (and any other
+/// textual form with the same value) to the same bytes, so producers on different
+/// languages/serializers derive identical hashes. Integers are rendered through
+/// `Number`'s own i64/u64 form (lossless even past 2^53); only true floats go
+/// through the `f64` path, with integral values dropping the fractional part and
+/// others getting a trimmed fixed-precision form.
+fn canonical_number(n: &serde_json::Number) -> String {
+    if n.is_i64() || n.is_u64() {
+        return n.to_string();
+    }
+
+    let value = n.as_f64().expect("document numbers must be finite");
+
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        let fixed = format!("{value:.12}");
+        let trimmed = fixed.trim_end_matches('0');
+        trimmed.trim_end_matches('.').to_string()
+    }
+}
+
+/// Append a JSON-escaped (quoted) copy of `s` to `out`.
+fn write_canonical_string(s: &str, out: &mut Vec<u8>) {
+    out.push(b'"');
+    for c in s.chars() {
+        match c {
+            '"' => out.extend_from_slice(b"\\\""),
+            '\\' => out.extend_from_slice(b"\\\\"),
+            '\n' => out.extend_from_slice(b"\\n"),
+            '\r' => out.extend_from_slice(b"\\r"),
+            '\t' => out.extend_from_slice(b"\\t"),
+            c if (c as u32) < 0x20 => out.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes()),
+            c => {
+                let mut buf = [0u8; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    out.push(b'"');
+}
+
+/// Recursively canonicalize `value` into `out`: object keys are sorted, numbers are
+/// normalized via `canonical_number`, and the result has no insignificant whitespace.
+fn write_canonical_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.extend_from_slice(b"null"),
+        Value::Bool(b) => out.extend_from_slice(if *b { b"true" } else { b"false" }),
+        Value::Number(n) => out.extend_from_slice(canonical_number(n).as_bytes()),
+        Value::String(s) => write_canonical_string(s, out),
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical_value(item, out);
+            }
+            out.push(b']');
+        }
+        Value::Object(map) => {
+            out.push(b'{');
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            for (i, key) in keys.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical_string(key, out);
+                out.push(b':');
+                write_canonical_value(&map[key.as_str()], out);
+            }
+            out.push(b'}');
+        }
+    }
+}
+
+/// Encode `document` as a deterministic byte sequence: object keys sorted, numbers
+/// normalized, and no insignificant whitespace, so that semantically identical
+/// documents (regardless of key order, formatting, or `50000` vs `50000.0`) hash
+/// the same everywhere.
+fn canonicalize_document(document: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_canonical_value(document, &mut out);
+    out
+}
+
+/// Leaf bytes for a document: its canonical encoding (see `canonicalize_document`).
+fn document_leaf_bytes(document: &Value) -> Vec<u8> {
+    canonicalize_document(document)
+}
+
+/// Parse `documents_json` into the leaf hashes used by the Merkle tree.
+fn document_leaves(documents_json: &str) -> Vec<[u8; 32]> {
+    let documents: Vec<Value> =
+        serde_json::from_str(documents_json).expect("Invalid documents JSON");
+
+    documents
+        .iter()
+        .map(|doc| merkle_leaf_hash(&document_leaf_bytes(doc)))
+        .collect()
+}
+
+/// Calculate the Merkle root over `documents_json` for attestation; this is
+/// what gets stored in `TEEAttestation.computation_hash`. An empty document
+/// set is a valid (if degenerate) input and hashes to `merkle_leaf_hash(&[])`
+/// rather than panicking.
+fn calculate_documents_hash(documents_json: &str) -> [u8; 32] {
+    let leaves = document_leaves(documents_json);
+    if leaves.is_empty() {
+        return merkle_leaf_hash(&[]);
+    }
+    let levels = build_merkle_levels(leaves);
+    *levels.last().unwrap().first().expect("build_merkle_levels always produces a last level")
+}
+
+/// Build an inclusion proof for the document at `index` in `documents_json`.
+/// Returns `None` if `index` is out of range.
+pub fn inclusion_proof(documents_json: &str, index: usize) -> Option<MerkleProof> {
+    let leaves = document_leaves(documents_json);
+    if index >= leaves.len() {
+        return None;
+    }
+
+    let levels = build_merkle_levels(leaves);
+    let mut siblings = Vec::with_capacity(levels.len() - 1);
+    let mut pos = index;
+
+    for level in &levels[..levels.len() - 1] {
+        let sibling_pos = if pos.is_multiple_of(2) {
+            if pos + 1 < level.len() { pos + 1 } else { pos }
+        } else {
+            pos - 1
+        };
+        siblings.push(level[sibling_pos]);
+        pos /= 2;
+    }
+
+    Some(MerkleProof { leaf_index: index, siblings })
+}
+
+/// Verify that `leaf` (the document's canonical encoding, see `canonicalize_document`,
+/// before leaf hashing) is included under `root`
+/// by folding `proof.siblings` back up from `proof.leaf_index`.
+pub fn verify_inclusion(leaf: &[u8], proof: &MerkleProof, root: [u8; 32]) -> bool {
+    let mut hash = merkle_leaf_hash(leaf);
+    let mut pos = proof.leaf_index;
+
+    for sibling in &proof.siblings {
+        hash = if pos.is_multiple_of(2) {
+            merkle_parent_hash(&hash, sibling)
+        } else {
+            merkle_parent_hash(sibling, &hash)
+        };
+        pos /= 2;
+    }
+
+    hash == root
+}
+
 /// Get current Unix timestamp in milliseconds
 fn get_timestamp_ms() -> u64 {
     SystemTime::now()
@@ -143,6 +505,51 @@ fn get_timestamp_ms() -> u64 {
         .as_millis() as u64
 }
 
+/// Build the deterministic preimage that gets Ed25519-signed (and re-verified):
+/// `kpi_value || computation_hash || timestamp`, plus `nonce` when replay protection
+/// is in use. See `AttestationSigner` for signing it outside this process.
+pub fn attestation_signing_preimage(
+    kpi_value: u64,
+    computation_hash: &[u8; 32],
+    timestamp: u64,
+    nonce: Option<u64>,
+) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(&kpi_value.to_le_bytes());
+    message.extend_from_slice(computation_hash);
+    message.extend_from_slice(&timestamp.to_le_bytes());
+    if let Some(nonce) = nonce {
+        message.extend_from_slice(&nonce.to_le_bytes());
+    }
+    message
+}
+
+/// Signs an `attestation_signing_preimage` without requiring direct access to
+/// the private key, so it can live behind an HSM or a remote TEE.
+pub trait AttestationSigner {
+    /// The Ed25519 public key corresponding to the signatures this signer produces.
+    fn public_key(&self) -> [u8; 32];
+
+    /// Sign `preimage` (as produced by `attestation_signing_preimage`) and return
+    /// the raw 64-byte Ed25519 signature.
+    fn sign(&self, preimage: &[u8]) -> [u8; 64];
+}
+
+/// Default `AttestationSigner` backed by an in-process `ed25519_dalek::Keypair`.
+pub struct LocalKeypairSigner<'a> {
+    pub keypair: &'a Keypair,
+}
+
+impl AttestationSigner for LocalKeypairSigner<'_> {
+    fn public_key(&self) -> [u8; 32] {
+        self.keypair.public.to_bytes()
+    }
+
+    fn sign(&self, preimage: &[u8]) -> [u8; 64] {
+        self.keypair.sign(preimage).to_bytes()
+    }
+}
+
 /// Main entry point for TEE execution (legacy, without attestation)
 pub fn calculate_kpi(json_str: &str, current_kpi: f64) -> KPIResult {
     let data: Value = serde_json::from_str(json_str).unwrap();
@@ -179,6 +586,32 @@ pub fn calculate_kpi(json_str: &str, current_kpi: f64) -> KPIResult {
 pub fn calculate_kpi_with_attestation(
     documents_json: &str,
     tee_keypair: &Keypair,
+) -> KPIResultWithAttestation {
+    let signer = LocalKeypairSigner { keypair: tee_keypair };
+    calculate_kpi_with_attestation_with_signer(documents_json, &signer, None)
+}
+
+/// Same as `calculate_kpi_with_attestation`, but folds `nonce` into the signed
+/// message and carries it in the attestation (as `AttestationVersion::V1`) so a
+/// verifier can reject replayed attestations via a strictly-increasing
+/// per-key nonce.
+pub fn calculate_kpi_with_attestation_with_nonce(
+    documents_json: &str,
+    tee_keypair: &Keypair,
+    nonce: u64,
+) -> KPIResultWithAttestation {
+    let signer = LocalKeypairSigner { keypair: tee_keypair };
+    calculate_kpi_with_attestation_with_signer(documents_json, &signer, Some(nonce))
+}
+
+/// Same as `calculate_kpi_with_attestation`, but signs through an arbitrary
+/// `AttestationSigner` instead of requiring direct access to an in-process
+/// `Keypair` — use this when the TEE private key is held by an HSM or a
+/// remote enclave.
+pub fn calculate_kpi_with_attestation_with_signer(
+    documents_json: &str,
+    signer: &dyn AttestationSigner,
+    nonce: Option<u64>,
 ) -> KPIResultWithAttestation {
     // Parse documents array
     let documents: Vec<Value> = serde_json::from_str(documents_json)
@@ -212,18 +645,12 @@ pub fn calculate_kpi_with_attestation(
     // Example: 1234.567 -> 1234567
     let kpi_value_u64 = (cumulative_kpi * 1000.0).round() as u64;
 
-    // Build message to sign: kpi_value || computation_hash || timestamp
-    let mut message = Vec::new();
-    message.extend_from_slice(&kpi_value_u64.to_le_bytes());
-    message.extend_from_slice(&computation_hash);
-    message.extend_from_slice(&timestamp.to_le_bytes());
-
-    // Sign the message
-    let signature_obj = tee_keypair.sign(&message);
-    let signature_bytes = signature_obj.to_bytes();
+    // Build message to sign: kpi_value || computation_hash || timestamp [|| nonce]
+    let message = attestation_signing_preimage(kpi_value_u64, &computation_hash, timestamp, nonce);
 
-    // Extract public key
-    let public_key_bytes = tee_keypair.public.to_bytes();
+    // Sign the message and fetch the signer's public key
+    let signature_bytes = signer.sign(&message);
+    let public_key_bytes = signer.public_key();
 
     // Create attestation
     let attestation = TEEAttestation {
@@ -232,6 +659,7 @@ pub fn calculate_kpi_with_attestation(
         timestamp,
         tee_public_key: public_key_bytes,
         signature: signature_bytes,
+        nonce,
     };
 
     // Convert attestation to bytes for blockchain submission
@@ -251,6 +679,375 @@ pub fn calculate_kpi_with_attestation(
     }
 }
 
+/// Errors produced while verifying a `TEEAttestation`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// `tee_public_key` is not a valid Ed25519 public key.
+    InvalidPublicKey,
+    /// `signature` is not a validly-encoded Ed25519 signature.
+    InvalidSignature,
+    /// The signature does not verify against the attestation's own fields.
+    SignatureMismatch,
+    /// `computation_hash` does not match the hash recomputed from the supplied documents.
+    HashMismatch,
+    /// `timestamp` is older than `FreshnessWindow::max_age_ms` relative to `now_ms`.
+    TooOld { timestamp: u64, now_ms: u64 },
+    /// `timestamp` is further in the future than `FreshnessWindow::max_skew_ms` allows.
+    TooFarInFuture { timestamp: u64, now_ms: u64 },
+    /// The attestation carries no nonce, but the caller requires replay protection.
+    MissingNonce,
+    /// `nonce` is not strictly greater than the last nonce seen for this signer.
+    NonceReplay { nonce: u64, last_seen: u64 },
+    /// An entry in `TEECommittee::public_keys` is not a valid compressed BLS12-381 G2 point.
+    InvalidCommitteePublicKey { index: usize },
+    /// `agg_signature` is not a valid compressed BLS12-381 G1 point.
+    InvalidAggregateSignature,
+    /// Fewer signers participated (per `signer_bitmap`) than `TEECommittee::threshold` requires.
+    BelowThreshold { required: usize, got: usize },
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyError::InvalidPublicKey => write!(f, "invalid Ed25519 public key"),
+            VerifyError::InvalidSignature => write!(f, "invalid Ed25519 signature encoding"),
+            VerifyError::SignatureMismatch => write!(f, "signature does not verify"),
+            VerifyError::HashMismatch => write!(f, "computation_hash does not match documents"),
+            VerifyError::TooOld { timestamp, now_ms } => {
+                write!(f, "attestation timestamp {timestamp} is too old (now={now_ms})")
+            }
+            VerifyError::TooFarInFuture { timestamp, now_ms } => {
+                write!(f, "attestation timestamp {timestamp} is too far in the future (now={now_ms})")
+            }
+            VerifyError::MissingNonce => write!(f, "attestation has no nonce but replay protection was requested"),
+            VerifyError::NonceReplay { nonce, last_seen } => {
+                write!(f, "nonce {nonce} is not greater than last seen nonce {last_seen}")
+            }
+            VerifyError::InvalidCommitteePublicKey { index } => {
+                write!(f, "committee public key at index {index} is not a valid BLS12-381 G2 point")
+            }
+            VerifyError::InvalidAggregateSignature => {
+                write!(f, "aggregate signature is not a valid BLS12-381 G1 point")
+            }
+            VerifyError::BelowThreshold { required, got } => {
+                write!(f, "aggregate attestation has {got} signers, below the required threshold of {required}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Verify `att`'s Ed25519 signature against its own `kpi_value`, `computation_hash`
+/// and `timestamp` (and `nonce`, if present). Does not check the documents or
+/// freshness — see `verify_attestation_with_documents` and `check_freshness`.
+pub fn verify_attestation(att: &TEEAttestation) -> Result<(), VerifyError> {
+    let public_key =
+        PublicKey::from_bytes(&att.tee_public_key).map_err(|_| VerifyError::InvalidPublicKey)?;
+    let signature =
+        Signature::from_bytes(&att.signature).map_err(|_| VerifyError::InvalidSignature)?;
+
+    let message = attestation_signing_preimage(att.kpi_value, &att.computation_hash, att.timestamp, att.nonce);
+
+    public_key
+        .verify(&message, &signature)
+        .map_err(|_| VerifyError::SignatureMismatch)
+}
+
+/// Like `verify_attestation`, but also recomputes `computation_hash` from
+/// `documents_json` and rejects the attestation if it doesn't match.
+pub fn verify_attestation_with_documents(
+    att: &TEEAttestation,
+    documents_json: &str,
+) -> Result<(), VerifyError> {
+    if calculate_documents_hash(documents_json) != att.computation_hash {
+        return Err(VerifyError::HashMismatch);
+    }
+    verify_attestation(att)
+}
+
+/// Acceptable clock drift for an attestation's `timestamp`, used to reject stale
+/// or future-dated attestations (a prerequisite for safe replay protection).
+pub struct FreshnessWindow {
+    pub max_age_ms: u64,
+    pub max_skew_ms: u64,
+}
+
+/// Check `timestamp` against `now_ms` under `window`.
+pub fn check_freshness(timestamp: u64, now_ms: u64, window: &FreshnessWindow) -> Result<(), VerifyError> {
+    if timestamp > now_ms.saturating_add(window.max_skew_ms) {
+        return Err(VerifyError::TooFarInFuture { timestamp, now_ms });
+    }
+    if now_ms.saturating_sub(timestamp) > window.max_age_ms {
+        return Err(VerifyError::TooOld { timestamp, now_ms });
+    }
+    Ok(())
+}
+
+/// Check that `nonce` is strictly greater than `last_seen_nonce` (the last nonce
+/// accepted for this signer), rejecting resubmission of an already-seen attestation.
+/// `last_seen_nonce` of `None` means no attestation has been accepted yet for this key.
+pub fn check_nonce(nonce: Option<u64>, last_seen_nonce: Option<u64>) -> Result<(), VerifyError> {
+    match (nonce, last_seen_nonce) {
+        (Some(nonce), Some(last_seen)) if nonce <= last_seen => {
+            Err(VerifyError::NonceReplay { nonce, last_seen })
+        }
+        (None, Some(_)) => Err(VerifyError::MissingNonce),
+        _ => Ok(()),
+    }
+}
+
+/// Domain separation tag for hashing attestation messages onto BLS12-381 G1,
+/// per the hash-to-curve suite used for `AggregatedTEEAttestation` signatures.
+const BLS_SIG_DST: &[u8] = b"WALRUS_KPI_BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+/// Domain separation tag for committee-registration proofs of possession. Kept
+/// distinct from `BLS_SIG_DST` so a PoP can never double as an attestation
+/// co-signature (or vice versa).
+const BLS_POP_DST: &[u8] = b"WALRUS_KPI_BLS_POP_BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+/// Hash an attestation's signed message onto BLS12-381 G1, matching the scheme
+/// each committee member signs with (G1 signatures, G2 public keys).
+fn hash_attestation_message_to_g1(message: &[u8]) -> G1Projective {
+    <G1Projective as HashToCurve<ExpandMsgXmd<sha2_0_9::Sha256>>>::hash_to_curve(message, BLS_SIG_DST)
+}
+
+/// Hash a candidate committee member's own public key onto BLS12-381 G1, for
+/// proof-of-possession signing/verification.
+fn hash_pop_message_to_g1(public_key: &[u8; 96]) -> G1Projective {
+    <G1Projective as HashToCurve<ExpandMsgXmd<sha2_0_9::Sha256>>>::hash_to_curve(public_key, BLS_POP_DST)
+}
+
+/// Sign `public_key`'s own bytes with the matching `secret_key`, producing the
+/// proof-of-possession `TEECommittee::register` requires before admitting that key.
+pub fn sign_proof_of_possession(secret_key: &bls12_381::Scalar, public_key: &[u8; 96]) -> [u8; 48] {
+    (hash_pop_message_to_g1(public_key) * secret_key).to_affine().to_compressed()
+}
+
+/// Errors produced while registering members into a `TEECommittee`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommitteeRegistrationError {
+    /// A member's public key is not a valid compressed BLS12-381 G2 point.
+    InvalidPublicKey { index: usize },
+    /// A member's proof-of-possession is not a valid compressed BLS12-381 G1 point.
+    InvalidProofOfPossession { index: usize },
+    /// A member's proof-of-possession does not verify against its own public key,
+    /// i.e. it fails to prove the registrant actually holds the matching secret key.
+    ProofOfPossessionMismatch { index: usize },
+}
+
+impl std::fmt::Display for CommitteeRegistrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommitteeRegistrationError::InvalidPublicKey { index } => {
+                write!(f, "public key at index {index} is not a valid BLS12-381 G2 point")
+            }
+            CommitteeRegistrationError::InvalidProofOfPossession { index } => {
+                write!(f, "proof of possession at index {index} is not a valid BLS12-381 G1 point")
+            }
+            CommitteeRegistrationError::ProofOfPossessionMismatch { index } => {
+                write!(f, "proof of possession at index {index} does not match its public key")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommitteeRegistrationError {}
+
+/// The known set of TEE committee members eligible to co-sign an aggregated
+/// attestation: each entry is a member's compressed BLS12-381 G2 public key,
+/// indexed by its position (which doubles as its bit in `signer_bitmap`).
+/// `threshold` is the minimum number of co-signers an aggregate must carry.
+///
+/// `aggregate_tee_attestations`/`verify_aggregated_attestation` combine these
+/// public keys by plain summation, which is only safe against rogue-key attacks
+/// (Boneh-Drijvers-Neven) if every key was admitted via `TEECommittee::register`,
+/// which requires each member to prove possession of its secret key first.
+#[derive(Debug)]
+pub struct TEECommittee {
+    public_keys: Vec<[u8; 96]>,
+    threshold: usize,
+}
+
+impl TEECommittee {
+    /// Register a committee from `(public_key, proof_of_possession)` pairs, rejecting
+    /// any member whose proof of possession doesn't verify against its own public key.
+    /// This is the only way to construct a `TEECommittee`, so an attacker who doesn't
+    /// hold a key's secret cannot get that key admitted to the committee.
+    pub fn register(
+        members: &[([u8; 96], [u8; 48])],
+        threshold: usize,
+    ) -> Result<TEECommittee, CommitteeRegistrationError> {
+        let mut public_keys = Vec::with_capacity(members.len());
+
+        for (index, (public_key, proof_of_possession)) in members.iter().enumerate() {
+            let pk_point = G2Affine::from_compressed(public_key)
+                .into_option()
+                .ok_or(CommitteeRegistrationError::InvalidPublicKey { index })?;
+            let pop_point = G1Affine::from_compressed(proof_of_possession)
+                .into_option()
+                .ok_or(CommitteeRegistrationError::InvalidProofOfPossession { index })?;
+
+            let lhs = pairing(&pop_point, &G2Affine::generator());
+            let rhs = pairing(&hash_pop_message_to_g1(public_key).to_affine(), &pk_point);
+            if lhs != rhs {
+                return Err(CommitteeRegistrationError::ProofOfPossessionMismatch { index });
+            }
+
+            public_keys.push(*public_key);
+        }
+
+        Ok(TEECommittee { public_keys, threshold })
+    }
+
+    pub fn public_keys(&self) -> &[[u8; 96]] {
+        &self.public_keys
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+}
+
+/// A KPI attestation co-signed by an *n*-of-*t* committee of independent TEEs.
+/// Carries one aggregate BLS12-381 signature rather than one signature per
+/// signer; `signer_bitmap` records which `TEECommittee::public_keys` indices
+/// participated (bit `i` set means committee member `i` co-signed).
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AggregatedTEEAttestation {
+    pub kpi_value: u64,
+    pub computation_hash: [u8; 32],
+    pub timestamp: u64,
+    pub signer_bitmap: u64,
+    #[serde(with = "BigArray")]
+    pub agg_signature: [u8; 48],
+}
+
+/// Errors produced while combining individual TEE signature shares into an
+/// `AggregatedTEEAttestation`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum AggregationError {
+    /// No signature shares were supplied.
+    NoSigners,
+    /// A committee index is out of range for `signer_bitmap`, which only has 64 bits.
+    SignerIndexOutOfRange { index: usize },
+    /// `signature_shares` is not a valid compressed BLS12-381 G1 point.
+    InvalidSignatureShare { index: usize },
+    /// Fewer shares were supplied than `TEECommittee::threshold` requires.
+    BelowThreshold { required: usize, got: usize },
+}
+
+impl std::fmt::Display for AggregationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AggregationError::NoSigners => write!(f, "no signature shares were supplied"),
+            AggregationError::SignerIndexOutOfRange { index } => {
+                write!(f, "signer index {index} does not fit in the 64-bit signer_bitmap")
+            }
+            AggregationError::InvalidSignatureShare { index } => {
+                write!(f, "signature share at index {index} is not a valid BLS12-381 G1 point")
+            }
+            AggregationError::BelowThreshold { required, got } => {
+                write!(f, "only {got} signature shares were supplied, below the required threshold of {required}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AggregationError {}
+
+/// Combine each co-signing TEE's individual BLS12-381 G1 signature (over the shared
+/// message `kpi_value || computation_hash || timestamp`) into one aggregate signature,
+/// by summing the G1 points. `signature_shares` pairs each signer's `TEECommittee`
+/// index with its compressed signature. Rejects aggregates below `committee.threshold()`.
+pub fn aggregate_tee_attestations(
+    kpi_value: u64,
+    computation_hash: [u8; 32],
+    timestamp: u64,
+    signature_shares: &[(usize, [u8; 48])],
+    committee: &TEECommittee,
+) -> Result<AggregatedTEEAttestation, AggregationError> {
+    if signature_shares.is_empty() {
+        return Err(AggregationError::NoSigners);
+    }
+    if signature_shares.len() < committee.threshold() {
+        return Err(AggregationError::BelowThreshold {
+            required: committee.threshold(),
+            got: signature_shares.len(),
+        });
+    }
+
+    let mut signer_bitmap = 0u64;
+    let mut agg_signature = G1Projective::identity();
+
+    for &(index, share) in signature_shares {
+        if index >= 64 {
+            return Err(AggregationError::SignerIndexOutOfRange { index });
+        }
+        let point = G1Affine::from_compressed(&share)
+            .into_option()
+            .ok_or(AggregationError::InvalidSignatureShare { index })?;
+
+        agg_signature += G1Projective::from(point);
+        signer_bitmap |= 1u64 << index;
+    }
+
+    Ok(AggregatedTEEAttestation {
+        kpi_value,
+        computation_hash,
+        timestamp,
+        signer_bitmap,
+        agg_signature: agg_signature.to_affine().to_compressed(),
+    })
+}
+
+/// Verify an `AggregatedTEEAttestation` against `committee`: check that at least
+/// `committee.threshold()` members participated, aggregate their public keys per
+/// `signer_bitmap`, and perform the pairing check
+/// `e(agg_signature, g2) == e(H(message), agg_public_key)`.
+pub fn verify_aggregated_attestation(
+    att: &AggregatedTEEAttestation,
+    committee: &TEECommittee,
+) -> Result<(), VerifyError> {
+    // Only bits that name an actual committee member count as participants.
+    let member_count = committee.public_keys().len();
+    let member_mask = if member_count >= 64 { u64::MAX } else { (1u64 << member_count) - 1 };
+    let participant_count = (att.signer_bitmap & member_mask).count_ones() as usize;
+    if participant_count < committee.threshold() {
+        return Err(VerifyError::BelowThreshold {
+            required: committee.threshold(),
+            got: participant_count,
+        });
+    }
+
+    let mut agg_public_key = G2Projective::identity();
+    for (index, compressed) in committee.public_keys().iter().enumerate() {
+        if att.signer_bitmap & (1u64 << index) == 0 {
+            continue;
+        }
+        let point = G2Affine::from_compressed(compressed)
+            .into_option()
+            .ok_or(VerifyError::InvalidCommitteePublicKey { index })?;
+        agg_public_key += G2Projective::from(point);
+    }
+
+    let agg_signature = G1Affine::from_compressed(&att.agg_signature)
+        .into_option()
+        .ok_or(VerifyError::InvalidAggregateSignature)?;
+
+    let message = attestation_signing_preimage(att.kpi_value, &att.computation_hash, att.timestamp, None);
+    let message_point = hash_attestation_message_to_g1(&message);
+
+    let lhs = pairing(&agg_signature, &G2Affine::generator());
+    let rhs = pairing(&message_point.to_affine(), &agg_public_key.to_affine());
+
+    if lhs == rhs {
+        Ok(())
+    } else {
+        Err(VerifyError::SignatureMismatch)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,8 +1081,8 @@ mod tests {
         // Sales Revenue: +50000, Payroll: -20000 => Total: 30000
         assert_eq!(result.kpi_result.kpi, 30000.0);
 
-        // Verify attestation bytes length
-        assert_eq!(result.attestation_bytes.len(), 144);
+        // Verify attestation bytes length (1 version byte + 144-byte v0 payload)
+        assert_eq!(result.attestation_bytes.len(), 145);
 
         // Verify attestation structure
         assert_eq!(result.attestation.kpi_value, 30000000); // 30000.0 * 1000
@@ -304,16 +1101,291 @@ mod tests {
 
         let result = calculate_kpi_with_attestation(documents_json, &tee_keypair);
 
-        // Verify we can deserialize the bytes back
-        let bytes = &result.attestation_bytes;
+        // Verify the versioned envelope round-trips through from_bytes
+        let decoded = TEEAttestation::from_bytes(&result.attestation_bytes)
+            .expect("valid envelope should decode");
 
-        // Extract kpi_value
-        let kpi_value = u64::from_le_bytes([
-            bytes[0], bytes[1], bytes[2], bytes[3],
-            bytes[4], bytes[5], bytes[6], bytes[7],
-        ]);
-
-        assert_eq!(kpi_value, result.attestation.kpi_value);
+        assert_eq!(decoded.kpi_value, result.attestation.kpi_value);
+        assert_eq!(decoded.computation_hash, result.attestation.computation_hash);
+        assert_eq!(decoded.signature, result.attestation.signature);
         println!("✅ Attestation serialization test passed");
     }
+
+    #[test]
+    fn test_attestation_rejects_unknown_version() {
+        let bytes = vec![0xFF; 145];
+        assert_eq!(
+            TEEAttestation::from_bytes(&bytes),
+            Err(AttestationError::UnknownVersion(0xFF))
+        );
+    }
+
+    #[test]
+    fn test_attestation_rejects_truncated_v0_payload() {
+        let bytes = vec![0u8; 10];
+        assert_eq!(
+            TEEAttestation::from_bytes(&bytes),
+            Err(AttestationError::InvalidLength { version: 0, expected: 144, got: 9 })
+        );
+    }
+
+    #[test]
+    fn test_merkle_inclusion_proof() {
+        let documents_json = r#"[
+            {"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 1000.0}]},
+            {"employeeDetails": {}, "grossPay": 200.0},
+            {"reportTitle": "Corporate Overhead Report", "totalOverheadCost": 300.0}
+        ]"#;
+
+        let root = calculate_documents_hash(documents_json);
+        let documents: Vec<Value> = serde_json::from_str(documents_json).unwrap();
+
+        for (i, doc) in documents.iter().enumerate() {
+            let proof = inclusion_proof(documents_json, i).expect("index in range");
+            let leaf_bytes = document_leaf_bytes(doc);
+            assert!(verify_inclusion(&leaf_bytes, &proof, root));
+        }
+
+        assert!(inclusion_proof(documents_json, documents.len()).is_none());
+    }
+
+    #[test]
+    fn test_merkle_inclusion_proof_rejects_wrong_root() {
+        let documents_json = r#"[
+            {"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 1000.0}]},
+            {"employeeDetails": {}, "grossPay": 200.0}
+        ]"#;
+
+        let documents: Vec<Value> = serde_json::from_str(documents_json).unwrap();
+        let proof = inclusion_proof(documents_json, 0).unwrap();
+        let leaf_bytes = document_leaf_bytes(&documents[0]);
+
+        assert!(!verify_inclusion(&leaf_bytes, &proof, [0u8; 32]));
+    }
+
+    #[test]
+    fn test_calculate_documents_hash_handles_empty_document_set() {
+        assert_eq!(calculate_documents_hash("[]"), merkle_leaf_hash(&[]));
+    }
+
+    #[test]
+    fn test_canonical_encoding_ignores_key_order_and_whitespace() {
+        let a: Value = serde_json::from_str(
+            r#"{"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 50000}]}"#,
+        )
+        .unwrap();
+        let b: Value = serde_json::from_str(
+            r#"{
+                "credits":   [ { "amount": 50000.0, "account": "Sales Revenue" } ] ,
+                "journalEntryId":"JE-001"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(canonicalize_document(&a), canonicalize_document(&b));
+    }
+
+    #[test]
+    fn test_canonical_encoding_distinguishes_different_values() {
+        let a: Value = serde_json::from_str(r#"{"amount": 50000}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"amount": 50001}"#).unwrap();
+
+        assert_ne!(canonicalize_document(&a), canonicalize_document(&b));
+    }
+
+    #[test]
+    fn test_canonical_encoding_preserves_large_integer_precision() {
+        let a: Value = serde_json::from_str(r#"{"amount": 9007199254740993}"#).unwrap();
+        let b: Value = serde_json::from_str(r#"{"amount": 9007199254740992}"#).unwrap();
+
+        assert_ne!(canonicalize_document(&a), canonicalize_document(&b));
+    }
+
+    #[test]
+    fn test_verify_attestation_accepts_valid() {
+        let mut csprng = OsRng{};
+        let tee_keypair = Keypair::generate(&mut csprng);
+        let documents_json = r#"[{"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 1000.0}]}]"#;
+
+        let result = calculate_kpi_with_attestation(documents_json, &tee_keypair);
+
+        assert!(verify_attestation(&result.attestation).is_ok());
+        assert!(verify_attestation_with_documents(&result.attestation, documents_json).is_ok());
+    }
+
+    #[test]
+    fn test_verify_attestation_rejects_tampered_kpi() {
+        let mut csprng = OsRng{};
+        let tee_keypair = Keypair::generate(&mut csprng);
+        let documents_json = r#"[{"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 1000.0}]}]"#;
+
+        let mut result = calculate_kpi_with_attestation(documents_json, &tee_keypair);
+        result.attestation.kpi_value += 1;
+
+        assert_eq!(verify_attestation(&result.attestation), Err(VerifyError::SignatureMismatch));
+    }
+
+    #[test]
+    fn test_verify_attestation_rejects_mismatched_documents() {
+        let mut csprng = OsRng{};
+        let tee_keypair = Keypair::generate(&mut csprng);
+        let documents_json = r#"[{"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 1000.0}]}]"#;
+        let other_documents_json = r#"[{"journalEntryId": "JE-002", "credits": [{"account": "Sales Revenue", "amount": 2000.0}]}]"#;
+
+        let result = calculate_kpi_with_attestation(documents_json, &tee_keypair);
+
+        assert_eq!(
+            verify_attestation_with_documents(&result.attestation, other_documents_json),
+            Err(VerifyError::HashMismatch)
+        );
+    }
+
+    #[test]
+    fn test_freshness_window() {
+        let window = FreshnessWindow { max_age_ms: 1_000, max_skew_ms: 100 };
+
+        assert!(check_freshness(10_000, 10_500, &window).is_ok());
+        assert_eq!(
+            check_freshness(8_000, 10_000, &window),
+            Err(VerifyError::TooOld { timestamp: 8_000, now_ms: 10_000 })
+        );
+        assert_eq!(
+            check_freshness(10_200, 10_000, &window),
+            Err(VerifyError::TooFarInFuture { timestamp: 10_200, now_ms: 10_000 })
+        );
+    }
+
+    #[test]
+    fn test_nonce_replay_protection() {
+        let mut csprng = OsRng{};
+        let tee_keypair = Keypair::generate(&mut csprng);
+        let documents_json = r#"[{"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 1000.0}]}]"#;
+
+        let result = calculate_kpi_with_attestation_with_nonce(documents_json, &tee_keypair, 5);
+        assert!(verify_attestation(&result.attestation).is_ok());
+        assert_eq!(result.attestation.nonce, Some(5));
+
+        // Same nonce (or an older one) must be rejected as a replay.
+        assert_eq!(check_nonce(Some(5), Some(5)), Err(VerifyError::NonceReplay { nonce: 5, last_seen: 5 }));
+        assert_eq!(check_nonce(Some(4), Some(5)), Err(VerifyError::NonceReplay { nonce: 4, last_seen: 5 }));
+        assert!(check_nonce(Some(6), Some(5)).is_ok());
+    }
+
+    /// An `AttestationSigner` that signs through a `Keypair` it owns, standing
+    /// in for a signer backed by an HSM or remote enclave.
+    struct RemoteStubSigner {
+        keypair: Keypair,
+    }
+
+    impl AttestationSigner for RemoteStubSigner {
+        fn public_key(&self) -> [u8; 32] {
+            self.keypair.public.to_bytes()
+        }
+
+        fn sign(&self, preimage: &[u8]) -> [u8; 64] {
+            self.keypair.sign(preimage).to_bytes()
+        }
+    }
+
+    #[test]
+    fn test_calculate_kpi_with_attestation_with_signer() {
+        let mut csprng = OsRng{};
+        let signer = RemoteStubSigner { keypair: Keypair::generate(&mut csprng) };
+        let documents_json = r#"[{"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 1000.0}]}]"#;
+
+        let result = calculate_kpi_with_attestation_with_signer(documents_json, &signer, None);
+        assert_eq!(result.attestation.tee_public_key, signer.public_key());
+        assert!(verify_attestation(&result.attestation).is_ok());
+    }
+
+    fn sample_bls_committee(n: usize) -> (Vec<bls12_381::Scalar>, TEECommittee) {
+        use bls12_381::Scalar;
+        use ff::Field;
+        use rand_core::OsRng;
+
+        let mut rng = OsRng;
+        let secrets: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let members: Vec<([u8; 96], [u8; 48])> = secrets
+            .iter()
+            .map(|sk| {
+                let public_key = (G2Affine::generator() * sk).to_affine().to_compressed();
+                let pop = sign_proof_of_possession(sk, &public_key);
+                (public_key, pop)
+            })
+            .collect();
+
+        let committee = TEECommittee::register(&members, 2).expect("valid proofs of possession");
+        (secrets, committee)
+    }
+
+    fn sample_bls_shares(secrets: &[bls12_381::Scalar], message: &[u8]) -> Vec<(usize, [u8; 48])> {
+        let h = hash_attestation_message_to_g1(message);
+        secrets
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| (i, (h * sk).to_affine().to_compressed()))
+            .collect()
+    }
+
+    #[test]
+    fn test_committee_registration_rejects_rogue_key_without_valid_pop() {
+        use bls12_381::Scalar;
+        use ff::Field;
+        use rand_core::OsRng;
+
+        let mut rng = OsRng;
+        let honest_sk = Scalar::random(&mut rng);
+        let honest_pk = (G2Affine::generator() * honest_sk).to_affine().to_compressed();
+        let honest_pop = sign_proof_of_possession(&honest_sk, &honest_pk);
+
+        // An attacker can construct an arbitrary public key (e.g. a function of
+        // `honest_pk` for a rogue-key attack), but cannot produce a matching PoP
+        // without the corresponding secret key.
+        let rogue_pk = (G2Affine::generator() * Scalar::random(&mut rng)).to_affine().to_compressed();
+
+        let err = TEECommittee::register(&[(honest_pk, honest_pop), (rogue_pk, honest_pop)], 2)
+            .unwrap_err();
+        assert_eq!(err, CommitteeRegistrationError::ProofOfPossessionMismatch { index: 1 });
+    }
+
+    #[test]
+    fn test_aggregated_attestation_threshold() {
+        let documents_json = r#"[{"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 1000.0}]}]"#;
+        let computation_hash = calculate_documents_hash(documents_json);
+        let kpi_value = 1_000_000u64;
+        let timestamp = 1_700_000_000_000u64;
+        let message = attestation_signing_preimage(kpi_value, &computation_hash, timestamp, None);
+
+        let (secrets, committee) = sample_bls_committee(3);
+        let shares = sample_bls_shares(&secrets, &message);
+
+        // Below threshold: only one of the three committee members signed.
+        assert_eq!(
+            aggregate_tee_attestations(kpi_value, computation_hash, timestamp, &shares[..1], &committee),
+            Err(AggregationError::BelowThreshold { required: 2, got: 1 })
+        );
+
+        let agg = aggregate_tee_attestations(kpi_value, computation_hash, timestamp, &shares, &committee)
+            .expect("threshold met");
+        assert_eq!(agg.signer_bitmap, 0b111);
+        assert!(verify_aggregated_attestation(&agg, &committee).is_ok());
+    }
+
+    #[test]
+    fn test_aggregated_attestation_rejects_tampered_kpi() {
+        let documents_json = r#"[{"journalEntryId": "JE-001", "credits": [{"account": "Sales Revenue", "amount": 1000.0}]}]"#;
+        let computation_hash = calculate_documents_hash(documents_json);
+        let kpi_value = 1_000_000u64;
+        let timestamp = 1_700_000_000_000u64;
+        let message = attestation_signing_preimage(kpi_value, &computation_hash, timestamp, None);
+
+        let (secrets, committee) = sample_bls_committee(2);
+        let shares = sample_bls_shares(&secrets, &message);
+
+        let mut agg = aggregate_tee_attestations(kpi_value, computation_hash, timestamp, &shares, &committee)
+            .expect("threshold met");
+        agg.kpi_value += 1;
+
+        assert_eq!(verify_aggregated_attestation(&agg, &committee), Err(VerifyError::SignatureMismatch));
+    }
 }